@@ -4,6 +4,15 @@ use crate::app::{
 };
 use super::line_numbers::LineNumberType::{Absolute, Relative};
 use super::line_numbers::LineNumbers;
+mod grapheme;
+mod motion;
+mod search;
+mod selection;
+mod wrap;
+use motion::{CharClass, ViMotion};
+use regex::Regex;
+use search::{SearchMatch, MAX_SEARCH_LINES};
+use selection::Selection;
 use ratatui::{
     buffer::Buffer as TUI_Buffer,
     layout::{Constraint, Direction, Layout, Rect},
@@ -31,6 +40,21 @@ pub struct ScreenBounds {
     rightmost_col: usize,
 }
 
+/// The shape `highlight_cursor` draws the cursor as. The surrounding app
+/// picks one per mode and focus state (e.g. `Block` in normal mode, `Beam`
+/// in insert mode, `HollowBlock` while the window is unfocused).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CursorStyle {
+    #[default]
+    Block,
+    Beam,
+    Underline,
+    HollowBlock,
+}
+
+/// `cursor.col` and `leftmost_col` count grapheme clusters, not bytes or
+/// display cells; the number of terminal columns each cluster contributes is
+/// only resolved at render time, via [`grapheme::display_width`].
 #[derive(Debug)]
 pub struct TextWindowState {
     pub top_line: usize,
@@ -40,6 +64,25 @@ pub struct TextWindowState {
     pub cur_vertical_percent: f32,
     pub cursor: BufferPosition,
     pub last_manual_col: usize,
+    /// Soft-wraps buffer lines at the window width instead of relying on
+    /// `leftmost_col` horizontal scrolling.
+    pub soft_wrap: bool,
+    /// Shape `highlight_cursor` draws the cursor as.
+    pub cursor_style: CursorStyle,
+    /// Index of the first visual row of `top_line` that's rendered, so a
+    /// single buffer line with more wrapped rows than the window is tall can
+    /// still be scrolled through. Set by the wrap-mode visual-row moves and
+    /// by wrap-mode `jump`s that scroll a row into view; reset to `0` by
+    /// `jump_to_last_line` and by jumps outside wrap mode, where every line
+    /// is a single row.
+    top_line_row: usize,
+    /// Maps each currently rendered row to the `(buffer_line, start_col,
+    /// end_col)` it shows, refreshed on every `build_lines` call while
+    /// `soft_wrap` is on; empty otherwise.
+    visual_rows: Vec<(usize, usize, usize)>,
+    selection: Option<Selection>,
+    search: Option<Regex>,
+    current_match: Option<SearchMatch>,
     buffer: Weak<Buffer>,
     theme: Weak<Theme>,
 }
@@ -54,13 +97,115 @@ impl TextWindowState {
             cur_vertical_percent: 0.0,
             cursor: BufferPosition { line: 0, col: 0 },
             last_manual_col: 0,
+            soft_wrap: false,
+            cursor_style: CursorStyle::default(),
+            top_line_row: 0,
+            visual_rows: Vec::new(),
+            selection: None,
+            search: None,
+            current_match: None,
             buffer,
             theme,
         };
     }
 
+    /// Compiles `pattern` as the active search; an empty or invalid pattern
+    /// just clears the current search instead of erroring.
+    pub fn set_search(&mut self, pattern: &str) {
+        self.search = if pattern.is_empty() {
+            None
+        } else {
+            Regex::new(pattern).ok()
+        };
+        self.current_match = None;
+    }
+
+    pub fn clear_search(&mut self) {
+        self.search = None;
+        self.current_match = None;
+    }
+
+    pub fn search_next(&mut self) {
+        if let Some(m) = self.find_match(true) {
+            self.current_match = Some(m);
+            self.jump(&BufferPosition {
+                line: m.line,
+                col: m.start_col,
+            });
+        }
+    }
+
+    pub fn search_prev(&mut self) {
+        if let Some(m) = self.find_match(false) {
+            self.current_match = Some(m);
+            self.jump(&BufferPosition {
+                line: m.line,
+                col: m.start_col,
+            });
+        }
+    }
+
+    fn line_matches(&self, search: &Regex, line: usize) -> Vec<SearchMatch> {
+        let buffer = self.buffer.upgrade().expect("searching a dead buffer!");
+        line_matches_in(&buffer, search, line)
+    }
+
+    /// Scans from the cursor for the next (or, going backward, the previous)
+    /// match, wrapping around the buffer but never looking further than
+    /// `MAX_SEARCH_LINES` lines away.
+    fn find_match(&self, forward: bool) -> Option<SearchMatch> {
+        let search = self.search.as_ref()?;
+        let lines_count = self.lines_count();
+        if lines_count == 0 {
+            return None;
+        }
+        let scan_range = min(MAX_SEARCH_LINES, lines_count);
+        for offset in 0..=scan_range {
+            let line = if forward {
+                (self.cursor.line + offset) % lines_count
+            } else {
+                (self.cursor.line + lines_count - offset % lines_count) % lines_count
+            };
+            let mut matches = self.line_matches(search, line);
+            if !forward {
+                matches.reverse();
+            }
+            let found = matches.into_iter().find(|m| {
+                if offset != 0 {
+                    return true;
+                }
+                if forward {
+                    m.start_col > self.cursor.col
+                } else {
+                    m.start_col < self.cursor.col
+                }
+            });
+            if found.is_some() {
+                return found;
+            }
+        }
+        None
+    }
+
+    pub fn start_selection(&mut self) {
+        self.selection = Some(Selection::from_single(&self.cursor));
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.selection = None;
+    }
+
+    fn update_selection(&mut self) {
+        if let Some(selection) = &mut self.selection {
+            selection.set_moving_point(&self.cursor);
+        }
+    }
+
     pub fn move_cursor(&mut self, dir: Rectilinear) {
         match dir {
+            Rectilinear::Up if self.soft_wrap => self.move_visual_up(),
+            Rectilinear::Down if self.soft_wrap => self.move_visual_down(),
+
             Rectilinear::Up => {
                 if self.cursor.line <= 0 {
                     return;
@@ -124,8 +269,12 @@ impl TextWindowState {
                 }
                 self.cursor.col += 1;
                 self.last_manual_col = self.cursor.col;
-                if self.cursor.col >= self.leftmost_col + self.last_width {
-                    self.leftmost_col += 1;
+                // Compare against the display-column edge, not a flat
+                // `leftmost_col + last_width`, so a wide glyph crossing the
+                // window's right edge scrolls by its full cell width.
+                if self.cursor.col > self.rightmost_col_on(self.cursor.line) {
+                    self.leftmost_col =
+                        self.col_back_from(self.cursor.line, self.cursor.col + 1, self.last_width);
                 }
             }
 
@@ -140,13 +289,306 @@ impl TextWindowState {
                 }
             }
         }
+        self.update_selection();
+    }
+
+    fn line_visual_rows(&self, line: usize) -> Vec<wrap::VisualRow> {
+        let graphemes = self.line_graphemes(line);
+        let refs: Vec<&str> = graphemes.iter().map(String::as_str).collect();
+        wrap::visual_rows(&refs, self.last_width)
+    }
+
+    /// Whether the given visual row of `line` was part of the window's last
+    /// render, per `self.visual_rows`.
+    fn is_visual_row_rendered(&self, line: usize, row: wrap::VisualRow) -> bool {
+        self.visual_rows
+            .iter()
+            .any(|&(l, start, end)| l == line && start == row.start_col && end == row.end_col)
+    }
+
+    fn move_visual_up(&mut self) {
+        let rows = self.line_visual_rows(self.cursor.line);
+        let row_idx = rows
+            .iter()
+            .position(|row| self.cursor.col < row.end_col || row.end_col == row.start_col)
+            .unwrap_or(rows.len() - 1);
+        let offset = self.cursor.col - rows[row_idx].start_col;
+
+        if row_idx > 0 {
+            let target_idx = row_idx - 1;
+            let row = rows[target_idx];
+            self.cursor.col = row.start_col + min(offset, (row.end_col - row.start_col).saturating_sub(1));
+            // A single line can wrap into more rows than the window is tall;
+            // if the target row scrolled above what's currently rendered,
+            // bring this line to the top of the window at that row.
+            if !self.is_visual_row_rendered(self.cursor.line, row) {
+                self.top_line = self.cursor.line;
+                self.top_line_row = target_idx;
+                self.cur_vertical_percent = 0.0;
+            }
+            return;
+        }
+        if self.cursor.line == 0 {
+            return;
+        }
+        self.cursor.line -= 1;
+        let rows = self.line_visual_rows(self.cursor.line);
+        let row = *rows.last().expect("a line always has at least one visual row");
+        self.cursor.col = row.start_col + min(offset, (row.end_col - row.start_col).saturating_sub(1));
+        self.jump(&self.cursor.clone());
+    }
+
+    fn move_visual_down(&mut self) {
+        let rows = self.line_visual_rows(self.cursor.line);
+        let row_idx = rows
+            .iter()
+            .position(|row| self.cursor.col < row.end_col || row.end_col == row.start_col)
+            .unwrap_or(rows.len() - 1);
+        let offset = self.cursor.col - rows[row_idx].start_col;
+
+        if row_idx + 1 < rows.len() {
+            let target_idx = row_idx + 1;
+            let row = rows[target_idx];
+            self.cursor.col = row.start_col + min(offset, (row.end_col - row.start_col).saturating_sub(1));
+            // Same as above, but scrolling down: bring the target row to the
+            // bottom of the window instead of the top.
+            if !self.is_visual_row_rendered(self.cursor.line, row) {
+                self.top_line = self.cursor.line;
+                self.top_line_row = target_idx.saturating_sub(self.last_height.saturating_sub(1));
+                self.cur_vertical_percent = 1.0;
+            }
+            return;
+        }
+        if self.cursor.line + 1 >= self.lines_count() {
+            return;
+        }
+        self.cursor.line += 1;
+        let rows = self.line_visual_rows(self.cursor.line);
+        let row = rows[0];
+        self.cursor.col = row.start_col + min(offset, (row.end_col - row.start_col).saturating_sub(1));
+        self.jump(&self.cursor.clone());
+    }
+
+    pub fn move_cursor_motion(&mut self, motion: ViMotion) {
+        if self.lines_count() == 0 {
+            return;
+        }
+        let target = match motion {
+            ViMotion::WordForward => self.find_word_forward(CharClass::of),
+            ViMotion::WordBackward => self.find_word_backward(CharClass::of),
+            ViMotion::WordEnd => self.find_word_end(CharClass::of),
+            ViMotion::BigWordForward => self.find_word_forward(CharClass::of_big),
+            ViMotion::BigWordBackward => self.find_word_backward(CharClass::of_big),
+            ViMotion::BigWordEnd => self.find_word_end(CharClass::of_big),
+            ViMotion::FirstNonBlank => self.find_first_non_blank(),
+            ViMotion::EndOfLine => self.find_end_of_line(),
+            ViMotion::MatchingBracket => self.find_matching_bracket(),
+            ViMotion::ScreenTop => self.find_screen_top(),
+            ViMotion::ScreenMiddle => self.find_screen_middle(),
+            ViMotion::ScreenBottom => self.find_screen_bottom(),
+        };
+        if let Some(target) = target {
+            self.jump(&target);
+            self.update_selection();
+        }
+    }
+
+    fn line_graphemes(&self, line: usize) -> Vec<String> {
+        let buffer = self.buffer.upgrade().expect("reading a dead buffer!");
+        grapheme::graphemes(&buffer.lines[line])
+            .into_iter()
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn char_at(&self, pos: &BufferPosition) -> Option<char> {
+        self.line_graphemes(pos.line)
+            .get(pos.col)?
+            .chars()
+            .next()
+    }
+
+    fn class_at(&self, pos: &BufferPosition, classify: fn(char) -> CharClass) -> CharClass {
+        match self.char_at(pos) {
+            Some(c) => classify(c),
+            None => CharClass::Whitespace,
+        }
+    }
+
+    fn step_forward(&self, pos: BufferPosition) -> Option<BufferPosition> {
+        if pos.col + 1 < self.line_length(pos.line) {
+            Some(BufferPosition {
+                line: pos.line,
+                col: pos.col + 1,
+            })
+        } else if pos.line + 1 < self.lines_count() {
+            Some(BufferPosition {
+                line: pos.line + 1,
+                col: 0,
+            })
+        } else {
+            None
+        }
+    }
+
+    fn step_backward(&self, pos: BufferPosition) -> Option<BufferPosition> {
+        if pos.col > 0 {
+            Some(BufferPosition {
+                line: pos.line,
+                col: pos.col - 1,
+            })
+        } else if pos.line > 0 {
+            Some(BufferPosition {
+                line: pos.line - 1,
+                col: self.line_length(pos.line - 1).saturating_sub(1),
+            })
+        } else {
+            None
+        }
+    }
+
+    fn find_word_forward(&self, classify: fn(char) -> CharClass) -> Option<BufferPosition> {
+        let start_class = self.class_at(&self.cursor, classify);
+        let mut pos = self.cursor.clone();
+        if start_class != CharClass::Whitespace {
+            while self.class_at(&pos, classify) == start_class {
+                pos = self.step_forward(pos)?;
+            }
+        }
+        while self.class_at(&pos, classify) == CharClass::Whitespace {
+            pos = self.step_forward(pos)?;
+        }
+        Some(pos)
+    }
+
+    fn find_word_backward(&self, classify: fn(char) -> CharClass) -> Option<BufferPosition> {
+        let mut pos = self.step_backward(self.cursor.clone())?;
+        while self.class_at(&pos, classify) == CharClass::Whitespace {
+            pos = self.step_backward(pos)?;
+        }
+        let class = self.class_at(&pos, classify);
+        while let Some(prev) = self.step_backward(pos.clone()) {
+            if self.class_at(&prev, classify) != class {
+                break;
+            }
+            pos = prev;
+        }
+        Some(pos)
+    }
+
+    fn find_word_end(&self, classify: fn(char) -> CharClass) -> Option<BufferPosition> {
+        let mut pos = self.step_forward(self.cursor.clone())?;
+        while self.class_at(&pos, classify) == CharClass::Whitespace {
+            pos = self.step_forward(pos)?;
+        }
+        let class = self.class_at(&pos, classify);
+        while let Some(next) = self.step_forward(pos.clone()) {
+            if self.class_at(&next, classify) != class {
+                break;
+            }
+            pos = next;
+        }
+        Some(pos)
+    }
+
+    fn find_first_non_blank(&self) -> Option<BufferPosition> {
+        let graphemes = self.line_graphemes(self.cursor.line);
+        let col = graphemes
+            .iter()
+            .position(|g| CharClass::of(g.chars().next().unwrap_or(' ')) != CharClass::Whitespace)
+            .unwrap_or(0);
+        Some(BufferPosition {
+            line: self.cursor.line,
+            col,
+        })
+    }
+
+    fn find_end_of_line(&self) -> Option<BufferPosition> {
+        Some(BufferPosition {
+            line: self.cursor.line,
+            col: self.line_length(self.cursor.line).saturating_sub(1),
+        })
+    }
+
+    fn find_matching_bracket(&self) -> Option<BufferPosition> {
+        const BRACKET_PAIRS: [(char, char); 3] = [('(', ')'), ('[', ']'), ('{', '}')];
+
+        let current = self.char_at(&self.cursor)?;
+        let (open, close, forward) = BRACKET_PAIRS.iter().find_map(|&(open, close)| {
+            if current == open {
+                Some((open, close, true))
+            } else if current == close {
+                Some((open, close, false))
+            } else {
+                None
+            }
+        })?;
+
+        let mut depth: i32 = 1;
+        let mut pos = self.cursor.clone();
+        loop {
+            pos = if forward {
+                self.step_forward(pos)?
+            } else {
+                self.step_backward(pos)?
+            };
+            match self.char_at(&pos) {
+                Some(c) if c == open => depth += if forward { 1 } else { -1 },
+                Some(c) if c == close => depth += if forward { -1 } else { 1 },
+                _ => {}
+            }
+            if depth == 0 {
+                return Some(pos);
+            }
+        }
+    }
+
+    /// The buffer line rendered at a given fraction of the way down the
+    /// window. In wrap mode `top_line + last_height - 1` isn't necessarily
+    /// on screen at all (a single over-tall wrapped line can fill the whole
+    /// window), so the line is read back from `visual_rows`, the record of
+    /// what was actually drawn, instead of recomputed from `last_height`.
+    fn rendered_line_at(&self, fraction: f32) -> usize {
+        if self.soft_wrap {
+            if self.visual_rows.is_empty() {
+                return self.top_line;
+            }
+            let idx = ((self.visual_rows.len() - 1) as f32 * fraction).round() as usize;
+            return self.visual_rows[idx].0;
+        }
+        let offset = (self.last_height.saturating_sub(1) as f32 * fraction).round() as usize;
+        min(self.top_line + offset, self.lines_count().saturating_sub(1))
+    }
+
+    fn find_screen_top(&self) -> Option<BufferPosition> {
+        Some(BufferPosition {
+            line: self.rendered_line_at(0.0),
+            col: self.cursor.col,
+        })
     }
 
-    fn screen_bounds(&self) -> ScreenBounds {
+    fn find_screen_middle(&self) -> Option<BufferPosition> {
+        Some(BufferPosition {
+            line: self.rendered_line_at(0.5),
+            col: self.cursor.col,
+        })
+    }
+
+    fn find_screen_bottom(&self) -> Option<BufferPosition> {
+        Some(BufferPosition {
+            line: self.rendered_line_at(1.0),
+            col: self.cursor.col,
+        })
+    }
+
+    /// `rightmost_col` is resolved against `line`'s own glyphs, since a wide
+    /// glyph's display width means the grapheme-index edge of the scroll
+    /// window isn't the same for every line.
+    fn screen_bounds(&self, line: usize) -> ScreenBounds {
         let top_line = self.top_line;
         let bottom_line = top_line + self.last_height - 1;
         let leftmost_col = self.leftmost_col;
-        let rightmost_col = leftmost_col + self.last_width - 1;
+        let rightmost_col = self.rightmost_col_on(line);
         return ScreenBounds {
             top_line,
             bottom_line,
@@ -155,13 +597,27 @@ impl TextWindowState {
         };
     }
 
+    /// In wrap mode a buffer line can span more visual rows than the window
+    /// is tall, so being within `[top_line, bottom_line]` doesn't mean
+    /// `pos`'s own row was actually drawn; checked against `visual_rows`,
+    /// the record of what `build_wrapped_lines` last rendered, instead.
+    /// Horizontal scrolling doesn't apply in wrap mode, so only the
+    /// vertical check matters there.
     fn is_on_screen(&self, pos: &BufferPosition) -> bool {
+        if self.soft_wrap {
+            let rows = self.line_visual_rows(pos.line);
+            let row_idx = rows
+                .iter()
+                .position(|row| pos.col < row.end_col || row.end_col == row.start_col)
+                .unwrap_or(rows.len() - 1);
+            return self.is_visual_row_rendered(pos.line, rows[row_idx]);
+        }
         let ScreenBounds {
             top_line,
             bottom_line,
             leftmost_col,
             rightmost_col,
-        } = self.screen_bounds();
+        } = self.screen_bounds(pos.line);
         let BufferPosition { line, col } = *pos;
         let within_vertically = line >= top_line && line <= bottom_line;
         let within_horizontally = col >= leftmost_col && col <= rightmost_col;
@@ -185,21 +641,69 @@ impl TextWindowState {
         self.cursor.col = pos.col;
         let relative_line = pos.line - self.top_line;
         self.cur_vertical_percent = relative_line as f32 / (self.last_height - 1) as f32;
+        // In wrap mode `pos`'s row is already rendered, so keep the scroll.
+        if !self.soft_wrap {
+            self.top_line_row = 0;
+        }
         self.snap_to_EOL();
     }
 
+    /// Steps `(line, row_idx)` back `rows_before` visual rows, clamping at
+    /// the start of the buffer, for scrolling a wrap-mode jump target into
+    /// roughly the middle of the window rather than just its first row.
+    fn scroll_back_rows(&self, mut line: usize, mut row_idx: usize, mut rows_before: usize) -> (usize, usize) {
+        while rows_before > 0 {
+            if row_idx > 0 {
+                row_idx -= 1;
+            } else if line > 0 {
+                line -= 1;
+                row_idx = self.line_visual_rows(line).len() - 1;
+            } else {
+                break;
+            }
+            rows_before -= 1;
+        }
+        (line, row_idx)
+    }
+
     pub fn jump(&mut self, pos: &BufferPosition) {
         if self.is_on_screen(pos) {
             self.jump_within_screen(pos);
             return;
         }
         let BufferPosition { line, col } = *pos;
+
+        if self.soft_wrap {
+            // `top_line`/`top_line_row` are the window's scroll position in
+            // visual-row units in wrap mode, so they have to be recomputed
+            // directly instead of via `cur_vertical_percent`, which only
+            // `build_lines`'s non-wrap path consults.
+            if self.lines_count() > 0 {
+                let line = min(line, self.lines_count() - 1);
+                let rows = self.line_visual_rows(line);
+                let row_idx = rows
+                    .iter()
+                    .position(|row| col < row.end_col || row.end_col == row.start_col)
+                    .unwrap_or(rows.len() - 1);
+                let rows_before = self.last_height / 2;
+                let (top_line, top_line_row) = self.scroll_back_rows(line, row_idx, rows_before);
+                self.top_line = top_line;
+                self.top_line_row = top_line_row;
+                self.cur_vertical_percent = 0.0;
+                self.cursor.line = line;
+                self.cursor.col = col;
+            }
+            self.snap_to_EOL();
+            self.last_manual_col = self.cursor.col;
+            return;
+        }
+
         let ScreenBounds {
             top_line,
             bottom_line,
             leftmost_col,
             rightmost_col,
-        } = self.screen_bounds();
+        } = self.screen_bounds(line);
 
         let vertically_out_of_bounds = line < top_line || line > bottom_line;
         if self.lines_count() > 0 && vertically_out_of_bounds {
@@ -207,11 +711,16 @@ impl TextWindowState {
             let relative_line = min(self.last_height / 2, line);
             self.cursor.line = line;
             self.cur_vertical_percent = relative_line as f32 / (self.last_height - 1) as f32;
+            self.top_line_row = 0;
         }
 
         if col < leftmost_col || col > rightmost_col {
-            let relative_col = min(self.last_width * 3 / 4, col);
-            self.leftmost_col = col - relative_col;
+            // Scroll so `col` lands roughly 3/4 of the way across the
+            // window, measured in display cells rather than grapheme
+            // indices, so it ends up on a cell boundary even past a wide
+            // glyph.
+            let max_cells = self.last_width * 3 / 4;
+            self.leftmost_col = self.col_back_from(line, col, max_cells);
             self.cursor.col = col;
         }
 
@@ -227,18 +736,15 @@ impl TextWindowState {
             return;
         }
         self.cursor.col = line_length - 1;
-        let to_the_right = self.cursor.col >= self.leftmost_col + self.last_width;
-        let out_of_bounds = to_the_right || self.cursor.col < self.leftmost_col;
+        let out_of_bounds =
+            self.cursor.col > self.rightmost_col_on(self.cursor.line) || self.cursor.col < self.leftmost_col;
         if !out_of_bounds {
             return;
         }
-        if to_the_right {
-            self.leftmost_col = self.cursor.col + 1 - self.last_width;
-        } else if self.cursor.col >= self.last_width {
-            self.leftmost_col = self.cursor.col + 1 - self.last_width;
-        } else {
-            self.leftmost_col = 0;
-        }
+        // Scroll so the last glyph's full display width fits on screen,
+        // right-anchored — same display-column treatment as `jump`'s
+        // horizontal branch, just anchored to the end instead of 3/4 across.
+        self.leftmost_col = self.col_back_from(self.cursor.line, self.cursor.col + 1, self.last_width);
     }
 
     pub fn jump_to_home(&mut self) {
@@ -261,6 +767,7 @@ impl TextWindowState {
         self.cursor.line = line;
         let relative_line = line - self.top_line;
         self.cur_vertical_percent = relative_line as f32 / (self.last_height - 1) as f32;
+        self.top_line_row = 0;
         self.snap_to_EOL();
         self.last_manual_col = self.cursor.col;
     }
@@ -274,20 +781,157 @@ impl TextWindowState {
     }
 
     fn line_length(&self, line: usize) -> usize {
-        self.buffer
-            .upgrade()
-            .expect("checking line length in a dead buffer!")
-            .lines[line]
-            .len()
+        grapheme::graphemes(
+            &self
+                .buffer
+                .upgrade()
+                .expect("checking line length in a dead buffer!")
+                .lines[line],
+        )
+        .len()
+    }
+
+    /// Grapheme index of the rightmost glyph on `line` that still fits
+    /// entirely within `last_width` display cells of `leftmost_col`. Differs from the
+    /// flat `leftmost_col + last_width - 1` once a wide glyph sits in that
+    /// range, since such a glyph spans two display cells for one grapheme
+    /// index.
+    fn rightmost_col_on(&self, line: usize) -> usize {
+        let graphemes = self.line_graphemes(line);
+        let mut col = self.leftmost_col.min(graphemes.len());
+        let mut used = 0;
+        while col < graphemes.len() {
+            let width = grapheme::display_width(&[graphemes[col].as_str()]);
+            if used + width > self.last_width {
+                break;
+            }
+            used += width;
+            col += 1;
+        }
+        col.saturating_sub(1).max(self.leftmost_col)
+    }
+
+    /// Grapheme index on `line` such that the display-cell width of
+    /// `[index, col)` is as large as possible without exceeding `max_cells`
+    /// — the display-column analogue of `col.saturating_sub(max_cells)`,
+    /// used to scroll `leftmost_col` so a target column lands on a cell
+    /// boundary instead of splitting a wide glyph.
+    fn col_back_from(&self, line: usize, col: usize, max_cells: usize) -> usize {
+        let graphemes = self.line_graphemes(line);
+        let mut from = col.min(graphemes.len());
+        let mut used = 0;
+        while from > 0 {
+            let width = grapheme::display_width(&[graphemes[from - 1].as_str()]);
+            if used + width > max_cells {
+                break;
+            }
+            used += width;
+            from -= 1;
+        }
+        from
+    }
+}
+
+/// Applies `cursor_style` on top of a cell's existing (line/selection/match)
+/// style: `Block` reverses it as before, `Underline` adds a single modifier
+/// instead of reversing, and `Beam` leaves it untouched since its bar glyph
+/// already stands out against the unreversed background. `HollowBlock` is
+/// meant to draw only the cell's outline rather than a solid block, which a
+/// single terminal cell can't actually express; `BOLD | UNDERLINED` is
+/// reserved exclusively for this shape so it's at least distinguishable from
+/// every other cursor style, not a real hollow-box rendering.
+fn cursor_cell_style(style: Style, cursor_style: CursorStyle) -> Style {
+    match cursor_style {
+        CursorStyle::Block => style.add_modifier(Modifier::REVERSED),
+        CursorStyle::Beam => style,
+        CursorStyle::Underline => style.add_modifier(Modifier::UNDERLINED),
+        CursorStyle::HollowBlock => style.add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
     }
 }
 
+/// The text rendered in the cursor's own cell: the buffer's actual glyph for
+/// every shape except `Beam`, which replaces it with a thin bar so it reads
+/// as a beam rather than a highlighted character.
+fn cursor_glyph(actual: &str, cursor_style: CursorStyle) -> String {
+    match cursor_style {
+        CursorStyle::Beam => "▏".to_string(),
+        _ => actual.to_string(),
+    }
+}
+
+/// Flattens a rendered line into its graphemes and the effective (line-style
+/// patched by span-style) style each one renders with, so a later highlight
+/// pass can recolor part of it without discarding styling earlier passes
+/// applied.
+fn line_cell_styles<'a>(line: &'a Line) -> Vec<(&'a str, Style)> {
+    let mut cells = Vec::new();
+    for span in &line.spans {
+        let style = line.style.patch(span.style);
+        for grapheme in grapheme::graphemes(&span.content) {
+            cells.push((grapheme, style));
+        }
+    }
+    cells
+}
+
+/// Computes the selected column interval `[from, to]` (inclusive, already
+/// translated into the visible/scrolled column space) for a rendered buffer
+/// line, or `None` if that line falls outside the selection entirely.
+fn selection_interval(
+    selection: &Selection,
+    buffer_line: usize,
+    leftmost_col: usize,
+    rendered_len: usize,
+) -> Option<(usize, usize)> {
+    if rendered_len == 0 {
+        return None;
+    }
+    let (start, end) = selection.ordered();
+    if buffer_line < start.line || buffer_line > end.line {
+        return None;
+    }
+    let from = if buffer_line == start.line {
+        start.col.saturating_sub(leftmost_col)
+    } else {
+        0
+    };
+    let to = if buffer_line == end.line {
+        end.col.saturating_sub(leftmost_col)
+    } else {
+        rendered_len - 1
+    };
+    let from = min(from, rendered_len - 1);
+    let to = min(to, rendered_len - 1);
+    if from > to {
+        return None;
+    }
+    Some((from, to))
+}
+
+/// Finds every match of `search` on `buffer`'s given line, translated into
+/// grapheme-indexed buffer coordinates.
+fn line_matches_in(buffer: &Buffer, search: &Regex, line: usize) -> Vec<SearchMatch> {
+    let text = &buffer.lines[line];
+    search
+        .find_iter(text)
+        .map(|found| SearchMatch {
+            line,
+            start_col: grapheme::byte_to_col(text, found.start()),
+            end_col: grapheme::byte_to_col(text, found.end()),
+        })
+        .collect()
+}
+
 impl TextWindow {
     pub fn new(buffer: Weak<Buffer>, theme: Weak<Theme>) -> TextWindow {
         TextWindow { buffer, theme }
     }
 
     fn build_lines(&self, height: u16, width: usize, state: &mut TextWindowState) -> Vec<Line> {
+        if state.soft_wrap {
+            return self.build_wrapped_lines(height, width, state);
+        }
+
         let buffer = self
             .buffer
             .upgrade()
@@ -296,6 +940,7 @@ impl TextWindow {
 
         state.last_height = height.into();
         state.last_width = width;
+        state.visual_rows.clear();
         let cursor_rel_line: usize =
             (state.cur_vertical_percent * (height - 1) as f32).round() as usize;
         let top_line: usize = if state.cursor.line > cursor_rel_line {
@@ -310,49 +955,331 @@ impl TextWindow {
         return buffer.lines[top_line..last_line]
             .iter()
             .map(|line| {
-                if state.leftmost_col < line.len() {
-                    line[state.leftmost_col..].to_string()
+                let graphemes = grapheme::graphemes(line);
+                let visible = if state.leftmost_col < graphemes.len() {
+                    &graphemes[state.leftmost_col..]
                 } else {
-                    "".to_string()
-                }
+                    &[][..]
+                };
+                let pad = width.saturating_sub(grapheme::display_width(visible));
+                format!("{}{}", visible.concat(), " ".repeat(pad))
             })
-            .map(|line| Line::styled(format!("{line: <width$}"), line_style))
+            .map(|line| Line::styled(line, line_style))
             .collect();
     }
 
-    fn highlight_cursor(&self, lines: &mut Vec<Line>, state: &mut TextWindowState) {
+    /// Soft-wrap counterpart of [`Self::build_lines`]: instead of scrolling
+    /// horizontally, each buffer line starting at `state.top_line` (from its
+    /// `state.top_line_row`'th visual row onward) is broken into one or more
+    /// visual rows via [`wrap::visual_rows`], and `state.visual_rows` is
+    /// refreshed to map each rendered row back to the `(buffer_line,
+    /// start_col, end_col)` it shows.
+    fn build_wrapped_lines(&self, height: u16, width: usize, state: &mut TextWindowState) -> Vec<Line> {
+        let buffer = self
+            .buffer
+            .upgrade()
+            .expect("building lines from a dead buffer!");
+        let theme = self.theme.upgrade().expect("referencing dropped theme!");
+
+        state.last_height = height.into();
+        state.last_width = width;
+        state.visual_rows.clear();
+
+        let line_style = Style::default()
+            .fg(theme.text_foreground)
+            .bg(theme.text_background);
+
+        let mut out = Vec::new();
+        let mut buffer_line = state.top_line;
+        let mut skip_rows = state.top_line_row;
+        while out.len() < height as usize && buffer_line < state.lines_count() {
+            let graphemes = grapheme::graphemes(&buffer.lines[buffer_line]);
+            for row in wrap::visual_rows(&graphemes, width).into_iter().skip(skip_rows) {
+                if out.len() >= height as usize {
+                    break;
+                }
+                let visible = &graphemes[row.start_col..row.end_col];
+                let pad = width.saturating_sub(grapheme::display_width(visible));
+                out.push(Line::styled(
+                    format!("{}{}", visible.concat(), " ".repeat(pad)),
+                    line_style,
+                ));
+                state.visual_rows.push((buffer_line, row.start_col, row.end_col));
+            }
+            skip_rows = 0;
+            buffer_line += 1;
+        }
+        out
+    }
+
+    /// Shades every visible search match, except on the cursor's own line
+    /// (left to `highlight_cursor`). `highlight_selection` runs afterwards
+    /// and recolors the selected interval on top, so a selection always wins
+    /// where the two overlap.
+    fn highlight_search(&self, lines: &mut Vec<Line>, state: &TextWindowState) {
+        // Rows no longer map 1:1 to buffer lines once `soft_wrap` is on, so
+        // matches aren't shaded there; only the cursor's cell is styled, by
+        // `highlight_cursor` via `state.visual_rows`.
+        if state.soft_wrap {
+            return;
+        }
+        let Some(search) = &state.search else {
+            return;
+        };
+        let buffer = self.buffer.upgrade().expect("referencing dropped buffer!");
+        let theme = self.theme.upgrade().expect("referencing dropped theme!");
+        let normal_style = Style::default()
+            .fg(theme.text_foreground)
+            .bg(theme.text_background);
+        let match_style = Style::default()
+            .bg(theme.search_match_background)
+            .fg(theme.search_match_foreground);
+        let current_match_style = Style::default()
+            .bg(theme.search_current_match_background)
+            .fg(theme.search_current_match_foreground);
+
+        for (i, line) in lines.iter_mut().enumerate() {
+            let buffer_line = state.top_line + i;
+            if buffer_line == state.cursor.line {
+                continue;
+            }
+            let matches = line_matches_in(&buffer, search, buffer_line);
+            if matches.is_empty() {
+                continue;
+            }
+            let old_line: String = line.to_owned().into();
+            let graphemes = grapheme::graphemes(&old_line);
+
+            let mut spans = Vec::new();
+            let mut col = 0;
+            for m in matches {
+                if m.end_col <= state.leftmost_col {
+                    continue;
+                }
+                let from = m.start_col.saturating_sub(state.leftmost_col);
+                if from >= graphemes.len() {
+                    continue;
+                }
+                let to = min(
+                    m.end_col.saturating_sub(state.leftmost_col).saturating_sub(1),
+                    graphemes.len() - 1,
+                );
+                if from > to || from < col {
+                    continue;
+                }
+                if from > col {
+                    spans.push(Span::styled(graphemes[col..from].concat(), normal_style));
+                }
+                let style = if state.current_match == Some(m) {
+                    current_match_style
+                } else {
+                    match_style
+                };
+                spans.push(Span::styled(graphemes[from..=to].concat(), style));
+                col = to + 1;
+            }
+            if spans.is_empty() {
+                continue;
+            }
+            if col < graphemes.len() {
+                spans.push(Span::styled(graphemes[col..].concat(), normal_style));
+            }
+            *line = Line::from(spans);
+        }
+    }
+
+    /// Shades the selected interval of every visible line that falls inside
+    /// the active selection, except the cursor's own line, which is left to
+    /// `highlight_cursor` so the cursor cell stays layered on top.
+    fn highlight_selection(&self, lines: &mut Vec<Line>, state: &TextWindowState) {
+        // See the matching guard in `highlight_search`.
+        if state.soft_wrap {
+            return;
+        }
+        let Some(selection) = &state.selection else {
+            return;
+        };
+        let theme = self.theme.upgrade().expect("referencing dropped theme!");
+        let selected_style = Style::default()
+            .bg(theme.selection_background)
+            .fg(theme.selection_foreground);
+
+        for (i, line) in lines.iter_mut().enumerate() {
+            let buffer_line = state.top_line + i;
+            if buffer_line == state.cursor.line {
+                continue;
+            }
+            let cells = line_cell_styles(line);
+            let Some((from, to)) =
+                selection_interval(selection, buffer_line, state.leftmost_col, cells.len())
+            else {
+                continue;
+            };
+
+            // Recolor just the selected interval, composing with whatever
+            // style `highlight_search` already applied instead of flattening
+            // the rest of the line back to a plain, unstyled run.
+            let mut spans = Vec::new();
+            let mut run_start = 0;
+            let mut run_style = if run_start >= from && run_start <= to {
+                selected_style
+            } else {
+                cells[0].1
+            };
+            for idx in 1..cells.len() {
+                let style = if idx >= from && idx <= to {
+                    selected_style
+                } else {
+                    cells[idx].1
+                };
+                if style != run_style {
+                    spans.push(Span::styled(
+                        cells[run_start..idx]
+                            .iter()
+                            .map(|&(g, _)| g)
+                            .collect::<String>(),
+                        run_style,
+                    ));
+                    run_start = idx;
+                    run_style = style;
+                }
+            }
+            spans.push(Span::styled(
+                cells[run_start..].iter().map(|&(g, _)| g).collect::<String>(),
+                run_style,
+            ));
+            *line = Line::from(spans);
+        }
+    }
+
+    /// Styles the cursor's own cell and returns the on-screen `(col, row)`
+    /// of a wide glyph's hidden trailing cell and the style it should carry,
+    /// if the cursor sits on one; `render` repaints that cell afterwards,
+    /// since ratatui always resets a multi-width grapheme's trailing cell to
+    /// the default style (see `Buffer::set_stringn`), which would otherwise
+    /// leave half of the cursor's background unpainted.
+    fn highlight_cursor(
+        &self,
+        lines: &mut Vec<Line>,
+        state: &mut TextWindowState,
+    ) -> Option<(u16, u16, Style)> {
         if lines.is_empty() {
             lines.push(Line::from(String::from_iter(
                 repeat(" ").take(state.last_width - 1),
             )));
         }
 
-        if state.cursor.line < state.top_line {
-            return;
-        }
-        let line = state.cursor.line - state.top_line;
+        // In wrap mode, rows no longer map 1:1 to buffer lines, so the
+        // rendered row and in-row column come from the `visual_rows` cache
+        // `build_wrapped_lines` just refreshed, instead of `top_line`/`leftmost_col`.
+        let (line, col) = if state.soft_wrap {
+            let Some(row_idx) = state.visual_rows.iter().position(|&(buffer_line, start, end)| {
+                buffer_line == state.cursor.line
+                    && state.cursor.col >= start
+                    && (state.cursor.col < end || end == start)
+            }) else {
+                return None;
+            };
+            let (_, start, _) = state.visual_rows[row_idx];
+            (row_idx, state.cursor.col - start)
+        } else {
+            if state.cursor.line < state.top_line {
+                return None;
+            }
+            (
+                state.cursor.line - state.top_line,
+                state.cursor.col - state.leftmost_col,
+            )
+        };
         if line >= lines.len() {
-            return;
+            return None;
         }
 
         let theme = self.theme.upgrade().expect("referencing dropped theme!");
-        let col = state.cursor.col - state.leftmost_col;
         let line_style = Style::default()
             .bg(theme.selected_line_background)
             .fg(theme.selected_line_foreground);
-        let cur_style = line_style.add_modifier(Modifier::REVERSED);
+        let selected_style = Style::default()
+            .bg(theme.selection_background)
+            .fg(theme.selection_foreground);
+        let match_style = Style::default()
+            .bg(theme.search_match_background)
+            .fg(theme.search_match_foreground);
+        let current_match_style = Style::default()
+            .bg(theme.search_current_match_background)
+            .fg(theme.search_current_match_foreground);
+        let cur_style = cursor_cell_style(line_style, state.cursor_style);
 
         let old_line: String = lines[line].to_owned().into();
         if old_line.is_empty() {
-            lines[line] = Line::styled(" ", cur_style);
-            return;
+            lines[line] = Line::styled(cursor_glyph(" ", state.cursor_style), cur_style);
+            return None;
         }
+        let graphemes = grapheme::graphemes(&old_line);
 
-        let left_span = Span::styled(old_line[..col].to_string(), line_style);
-        let cur_span = Span::styled(old_line[col..col + 1].to_string(), cur_style);
-        let right_span = Span::styled(old_line[col + 1..].to_string(), line_style);
+        // Selection and search shading stay disabled in wrap mode (see the
+        // matching guards in `highlight_selection`/`highlight_search`), so
+        // only the cursor's own cell needs to be drawn there.
+        let highlight_range = if state.soft_wrap {
+            None
+        } else {
+            let selection_range = state.selection.as_ref().and_then(|selection| {
+                selection_interval(selection, state.cursor.line, state.leftmost_col, graphemes.len())
+                    .map(|range| (range, selected_style))
+            });
+            let buffer = self.buffer.upgrade().expect("referencing dropped buffer!");
+            let search_range = state.search.as_ref().and_then(|search| {
+                line_matches_in(&buffer, search, state.cursor.line)
+                    .into_iter()
+                    .find(|m| state.cursor.col >= m.start_col && state.cursor.col < m.end_col)
+                    .map(|m| {
+                        let from = m.start_col.saturating_sub(state.leftmost_col);
+                        let to = min(
+                            m.end_col.saturating_sub(state.leftmost_col).saturating_sub(1),
+                            graphemes.len() - 1,
+                        );
+                        let style = if state.current_match == Some(m) {
+                            current_match_style
+                        } else {
+                            match_style
+                        };
+                        ((from, to), style)
+                    })
+            });
+            // A selection always wins over search-match shading where the two overlap.
+            selection_range.or(search_range)
+        };
 
-        lines[line] = Line::from(vec![left_span, cur_span, right_span]);
+        let cursor_span_style = match highlight_range {
+            Some((_, hl_style)) => cursor_cell_style(hl_style, state.cursor_style),
+            None => cur_style,
+        };
+        let spans = match highlight_range {
+            Some(((from, to), hl_style)) => vec![
+                Span::styled(graphemes[..from].concat(), line_style),
+                Span::styled(graphemes[from..col].concat(), hl_style),
+                Span::styled(cursor_glyph(graphemes[col], state.cursor_style), cursor_span_style),
+                Span::styled(graphemes[col + 1..=to].concat(), hl_style),
+                Span::styled(graphemes[to + 1..].concat(), line_style),
+            ],
+            None => vec![
+                Span::styled(graphemes[..col].concat(), line_style),
+                Span::styled(cursor_glyph(graphemes[col], state.cursor_style), cursor_span_style),
+                Span::styled(graphemes[col + 1..].concat(), line_style),
+            ],
+        };
+
+        lines[line] = Line::from(spans);
+
+        // `Beam` substitutes a single-width glyph, so there's no hidden
+        // cell to repaint regardless of how wide the actual character is.
+        if state.cursor_style == CursorStyle::Beam
+            || grapheme::display_width(&graphemes[col..col + 1]) <= 1
+        {
+            return None;
+        }
+        let hidden_col = grapheme::display_width(&graphemes[..col]) + 1;
+        Some((hidden_col as u16, line as u16, cursor_span_style))
     }
 }
 
@@ -374,7 +1301,9 @@ impl StatefulWidget for TextWindow {
         let theme = self.theme.upgrade().expect("referencing dropped theme!");
         let lines_area = window_layout[2];
         let mut lines = self.build_lines(lines_area.height, lines_area.width.into(), state);
-        self.highlight_cursor(&mut lines, state);
+        self.highlight_search(&mut lines, state);
+        self.highlight_selection(&mut lines, state);
+        let wide_cursor_fixup = self.highlight_cursor(&mut lines, state);
         let line_numbers_area = window_layout[0];
         let line_hints_area = window_layout[1];
         let line_hints = Paragraph::new("").style(Style::default().bg(theme.text_background));
@@ -403,5 +1332,107 @@ impl StatefulWidget for TextWindow {
                 .render(gap_area, tui_buf);
         }
         Paragraph::new(lines).render(lines_area, tui_buf);
+
+        // Ratatui resets a wide glyph's hidden trailing cell to the default
+        // style while rendering the `Paragraph` above, so the cursor's
+        // background on a wide character has to be repainted afterwards.
+        if let Some((col, row, style)) = wide_cursor_fixup {
+            if col < lines_area.width && row < lines_area.height {
+                tui_buf.set_style(
+                    Rect::new(lines_area.x + col, lines_area.y + row, 1, 1),
+                    style,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    /// `theme` is a dangling `Weak` since none of `find_matching_bracket`'s
+    /// cursor-motion logic ever upgrades it; `buffer` must outlive the
+    /// returned state for its `Weak` to stay valid.
+    fn state_with(buffer: &Rc<Buffer>) -> TextWindowState {
+        TextWindowState::new(Rc::downgrade(buffer), Weak::new())
+    }
+
+    fn buffer_of(lines: &[&str]) -> Rc<Buffer> {
+        Rc::new(Buffer {
+            lines: lines.iter().map(|s| s.to_string()).collect(),
+        })
+    }
+
+    #[test]
+    fn finds_matching_close_paren_scanning_forward() {
+        let buffer = buffer_of(&["(a + (b))"]);
+        let mut state = state_with(&buffer);
+        state.cursor = BufferPosition { line: 0, col: 0 };
+        assert_eq!(
+            state.find_matching_bracket(),
+            Some(BufferPosition { line: 0, col: 8 })
+        );
+    }
+
+    #[test]
+    fn finds_matching_open_paren_scanning_backward() {
+        let buffer = buffer_of(&["(a + (b))"]);
+        let mut state = state_with(&buffer);
+        state.cursor = BufferPosition { line: 0, col: 8 };
+        assert_eq!(
+            state.find_matching_bracket(),
+            Some(BufferPosition { line: 0, col: 0 })
+        );
+    }
+
+    #[test]
+    fn matches_the_innermost_pair_first() {
+        let buffer = buffer_of(&["(a + (b))"]);
+        let mut state = state_with(&buffer);
+        state.cursor = BufferPosition { line: 0, col: 5 };
+        assert_eq!(
+            state.find_matching_bracket(),
+            Some(BufferPosition { line: 0, col: 7 })
+        );
+    }
+
+    #[test]
+    fn distinguishes_bracket_kinds() {
+        let buffer = buffer_of(&["[a (b)]"]);
+        let mut state = state_with(&buffer);
+        state.cursor = BufferPosition { line: 0, col: 0 };
+        assert_eq!(
+            state.find_matching_bracket(),
+            Some(BufferPosition { line: 0, col: 6 })
+        );
+    }
+
+    #[test]
+    fn returns_none_off_a_bracket() {
+        let buffer = buffer_of(&["(a)"]);
+        let mut state = state_with(&buffer);
+        state.cursor = BufferPosition { line: 0, col: 1 };
+        assert_eq!(state.find_matching_bracket(), None);
+    }
+
+    #[test]
+    fn returns_none_for_an_unbalanced_bracket() {
+        let buffer = buffer_of(&["(a"]);
+        let mut state = state_with(&buffer);
+        state.cursor = BufferPosition { line: 0, col: 0 };
+        assert_eq!(state.find_matching_bracket(), None);
+    }
+
+    #[test]
+    fn matching_bracket_can_span_lines() {
+        let buffer = buffer_of(&["(a", "b)"]);
+        let mut state = state_with(&buffer);
+        state.cursor = BufferPosition { line: 0, col: 0 };
+        assert_eq!(
+            state.find_matching_bracket(),
+            Some(BufferPosition { line: 1, col: 1 })
+        );
     }
 }
\ No newline at end of file
@@ -0,0 +1,25 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Splits a buffer line into the grapheme clusters that `BufferPosition::col`
+/// indexes into, so a column never lands inside a multi-byte character or a
+/// combining mark.
+pub(crate) fn graphemes(line: &str) -> Vec<&str> {
+    line.graphemes(true).collect()
+}
+
+/// Number of terminal cells a run of grapheme clusters occupies once
+/// rendered (0 for combining marks, 2 for wide CJK/emoji glyphs).
+pub(crate) fn display_width(graphemes: &[&str]) -> usize {
+    graphemes.iter().map(|g| UnicodeWidthStr::width(*g)).sum()
+}
+
+/// Converts a byte offset within `line` (e.g. from a `regex` match) into a
+/// grapheme index, so search results line up with `BufferPosition::col`: the
+/// number of clusters that start before the offset, so an offset inside a
+/// cluster maps to the index of the next one.
+pub(crate) fn byte_to_col(line: &str, byte_idx: usize) -> usize {
+    line.grapheme_indices(true)
+        .take_while(|&(start, _)| start < byte_idx)
+        .count()
+}
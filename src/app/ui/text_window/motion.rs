@@ -0,0 +1,79 @@
+/// Three-way classification of a character used to find vi-style word
+/// boundaries: a "word" is a maximal run of characters of the same class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CharClass {
+    Whitespace,
+    Word,
+    Punctuation,
+}
+
+impl CharClass {
+    pub(crate) fn of(c: char) -> Self {
+        if c.is_whitespace() {
+            CharClass::Whitespace
+        } else if c.is_alphanumeric() || c == '_' {
+            CharClass::Word
+        } else {
+            CharClass::Punctuation
+        }
+    }
+
+    /// Collapses `Word` and `Punctuation` into one class, so WORD motions
+    /// (`W`/`B`/`E`) only stop at whitespace.
+    pub(crate) fn of_big(c: char) -> Self {
+        if c.is_whitespace() {
+            CharClass::Whitespace
+        } else {
+            CharClass::Word
+        }
+    }
+}
+
+/// Vi-style motions, fed through `TextWindowState::move_cursor_motion` to
+/// compute a target `BufferPosition`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViMotion {
+    WordForward,
+    WordBackward,
+    WordEnd,
+    BigWordForward,
+    BigWordBackward,
+    BigWordEnd,
+    FirstNonBlank,
+    EndOfLine,
+    MatchingBracket,
+    ScreenTop,
+    ScreenMiddle,
+    ScreenBottom,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn of_classifies_whitespace() {
+        assert_eq!(CharClass::of(' '), CharClass::Whitespace);
+        assert_eq!(CharClass::of('\t'), CharClass::Whitespace);
+    }
+
+    #[test]
+    fn of_classifies_alphanumerics_and_underscore_as_word() {
+        assert_eq!(CharClass::of('a'), CharClass::Word);
+        assert_eq!(CharClass::of('9'), CharClass::Word);
+        assert_eq!(CharClass::of('_'), CharClass::Word);
+    }
+
+    #[test]
+    fn of_classifies_everything_else_as_punctuation() {
+        assert_eq!(CharClass::of('+'), CharClass::Punctuation);
+        assert_eq!(CharClass::of('('), CharClass::Punctuation);
+    }
+
+    #[test]
+    fn of_big_collapses_word_and_punctuation() {
+        assert_eq!(CharClass::of_big('a'), CharClass::Word);
+        assert_eq!(CharClass::of_big('+'), CharClass::Word);
+        assert_eq!(CharClass::of_big(' '), CharClass::Whitespace);
+    }
+}
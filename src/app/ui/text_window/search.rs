@@ -0,0 +1,13 @@
+/// Caps how many lines `TextWindowState::search_next`/`search_prev` scan
+/// away from the cursor, so a search on a huge buffer stays responsive
+/// instead of walking the whole thing.
+pub(crate) const MAX_SEARCH_LINES: usize = 10_000;
+
+/// A single search match in buffer coordinates. `start_col`/`end_col` are
+/// grapheme indices, with `end_col` exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SearchMatch {
+    pub line: usize,
+    pub start_col: usize,
+    pub end_col: usize,
+}
@@ -20,4 +20,64 @@ impl Selection {
             moving_point: moving.clone(),
         }
     }
+
+    pub fn set_moving_point(&mut self, bufpos: &BufferPosition) {
+        self.moving_point = bufpos.clone();
+    }
+
+    /// Returns the two endpoints ordered as `(start, end)` in (line, col) order,
+    /// regardless of which one is the fixed point.
+    pub fn ordered(&self) -> (BufferPosition, BufferPosition) {
+        let fixed_key = (self.fixed_point.line, self.fixed_point.col);
+        let moving_key = (self.moving_point.line, self.moving_point.col);
+        if fixed_key <= moving_key {
+            (self.fixed_point.clone(), self.moving_point.clone())
+        } else {
+            (self.moving_point.clone(), self.fixed_point.clone())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pos(line: usize, col: usize) -> BufferPosition {
+        BufferPosition { line, col }
+    }
+
+    #[test]
+    fn ordered_keeps_fixed_first_when_moving_point_is_later() {
+        let mut selection = Selection::from_single(&pos(1, 1));
+        selection.set_moving_point(&pos(3, 0));
+        assert_eq!(selection.ordered(), (pos(1, 1), pos(3, 0)));
+    }
+
+    #[test]
+    fn ordered_swaps_when_the_fixed_point_is_later() {
+        let mut selection = Selection::from_single(&pos(3, 0));
+        selection.set_moving_point(&pos(1, 1));
+        assert_eq!(selection.ordered(), (pos(1, 1), pos(3, 0)));
+    }
+
+    #[test]
+    fn ordered_breaks_a_same_line_tie_by_column() {
+        let mut selection = Selection::from_single(&pos(2, 5));
+        selection.set_moving_point(&pos(2, 1));
+        assert_eq!(selection.ordered(), (pos(2, 1), pos(2, 5)));
+    }
+
+    #[test]
+    fn ordered_is_stable_when_both_points_are_equal() {
+        let selection = Selection::from_single(&pos(4, 4));
+        assert_eq!(selection.ordered(), (pos(4, 4), pos(4, 4)));
+    }
+
+    #[test]
+    fn from_pair_preserves_which_point_is_fixed() {
+        let selection = Selection::from_pair(&pos(5, 0), &pos(2, 0));
+        assert_eq!(selection.fixed_point, pos(5, 0));
+        assert_eq!(selection.moving_point, pos(2, 0));
+        assert_eq!(selection.ordered(), (pos(2, 0), pos(5, 0)));
+    }
 }
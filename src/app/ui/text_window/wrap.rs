@@ -0,0 +1,163 @@
+use super::grapheme;
+
+/// A single visual row of a soft-wrapped buffer line, as a grapheme-index
+/// range `[start_col, end_col)` into that line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct VisualRow {
+    pub start_col: usize,
+    pub end_col: usize,
+}
+
+/// Breaks `graphemes` into visual rows of at most `width` display columns,
+/// preferring to break at the whitespace run closest to the limit and
+/// falling back to a hard break only when a single word is itself longer
+/// than `width`.
+pub(crate) fn visual_rows(graphemes: &[&str], width: usize) -> Vec<VisualRow> {
+    if graphemes.is_empty() {
+        return vec![VisualRow {
+            start_col: 0,
+            end_col: 0,
+        }];
+    }
+
+    let width = width.max(1);
+    let mut rows = Vec::new();
+    let mut row_start = 0;
+    while row_start < graphemes.len() {
+        let mut used = 0;
+        let mut end = row_start;
+        let mut last_break = None;
+        while end < graphemes.len() {
+            let glyph_width = grapheme::display_width(&graphemes[end..end + 1]);
+            if used + glyph_width > width && end > row_start {
+                break;
+            }
+            used += glyph_width;
+            if graphemes[end].chars().all(char::is_whitespace) {
+                last_break = Some(end + 1);
+            }
+            end += 1;
+        }
+        let row_end = if end < graphemes.len() {
+            last_break.filter(|&b| b > row_start).unwrap_or(end)
+        } else {
+            end
+        };
+        rows.push(VisualRow {
+            start_col: row_start,
+            end_col: row_end,
+        });
+        row_start = row_end;
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows_for(line: &str, width: usize) -> Vec<VisualRow> {
+        let graphemes = grapheme::graphemes(line);
+        visual_rows(&graphemes, width)
+    }
+
+    #[test]
+    fn empty_line_is_a_single_empty_row() {
+        assert_eq!(
+            rows_for("", 10),
+            vec![VisualRow {
+                start_col: 0,
+                end_col: 0,
+            }]
+        );
+    }
+
+    #[test]
+    fn short_line_fits_in_one_row() {
+        assert_eq!(
+            rows_for("hello", 10),
+            vec![VisualRow {
+                start_col: 0,
+                end_col: 5,
+            }]
+        );
+    }
+
+    #[test]
+    fn breaks_at_whitespace_closest_to_the_limit() {
+        // "hello world" at width 8: "hello " (6) fits, "world" doesn't, so the
+        // break lands at the space rather than mid-word.
+        assert_eq!(
+            rows_for("hello world", 8),
+            vec![
+                VisualRow {
+                    start_col: 0,
+                    end_col: 6,
+                },
+                VisualRow {
+                    start_col: 6,
+                    end_col: 11,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn word_wider_than_width_is_hard_broken() {
+        // No whitespace at all, so there's no break candidate and the row
+        // must be cut exactly at `width` graphemes.
+        assert_eq!(
+            rows_for("abcdefgh", 3),
+            vec![
+                VisualRow {
+                    start_col: 0,
+                    end_col: 3,
+                },
+                VisualRow {
+                    start_col: 3,
+                    end_col: 6,
+                },
+                VisualRow {
+                    start_col: 6,
+                    end_col: 8,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn trailing_whitespace_stays_on_its_own_row() {
+        // "ab   " at width 3: "ab " (3) fits as the first row, leaving the
+        // remaining two spaces as a second, shorter row.
+        assert_eq!(
+            rows_for("ab   ", 3),
+            vec![
+                VisualRow {
+                    start_col: 0,
+                    end_col: 3,
+                },
+                VisualRow {
+                    start_col: 3,
+                    end_col: 5,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn width_of_zero_is_clamped_to_one() {
+        assert_eq!(
+            rows_for("ab", 0),
+            vec![
+                VisualRow {
+                    start_col: 0,
+                    end_col: 1,
+                },
+                VisualRow {
+                    start_col: 1,
+                    end_col: 2,
+                },
+            ]
+        );
+    }
+}